@@ -26,6 +26,8 @@ mod runner;
 
 use runner::Runner;
 
+pub use runner::{JobDispatcher, JOB_DISPATCH_ALPN};
+
 #[derive(Debug)]
 pub struct JoinedLibraryCreateArgs {
 	pub pub_id: libraries::PubId,
@@ -92,6 +94,27 @@ pub enum UserResponse {
 		accepted: Option<BasicLibraryCreationArgs>,
 	},
 }
+
+/// Progress reported by a job running on a peer device, streamed back over
+/// the same connection that dispatched it. Mirrors the subset of
+/// `JobReportUpdate` that's meaningful across the wire.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub enum RemoteJobProgress {
+	TaskCount(usize),
+	CompletedTaskCount(usize),
+	Message(String),
+}
+
+/// Final outcome of a job dispatched to a peer, reported once the remote job
+/// manager settles it to a terminal `JobStatus`.
+#[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
+pub enum RemoteJobOutcome {
+	/// The remote job reached a terminal status; `status` is the `JobStatus`
+	/// discriminant as persisted locally.
+	Settled { status: i32 },
+	/// The connection to the peer was lost before the job settled.
+	PeerLost,
+}
 #[derive(Debug, Clone)]
 pub struct CloudP2P {
 	msgs_tx: flume::Sender<runner::Message>,
@@ -106,9 +129,10 @@ impl CloudP2P {
 		dns_origin_domain: String,
 		dns_pkarr_url: Url,
 		relay_url: RelayUrl,
+		job_dispatcher: std::sync::Arc<dyn JobDispatcher>,
 	) -> Result<Self, Error> {
 		let endpoint = Endpoint::builder()
-			.alpns(vec![CloudP2PALPN::LATEST.to_vec()])
+			.alpns(vec![CloudP2PALPN::LATEST.to_vec(), JOB_DISPATCH_ALPN.to_vec()])
 			.discovery(Box::new(ConcurrentDiscovery::from_services(vec![
 				Box::new(DnsDiscovery::new(dns_origin_domain)),
 				Box::new(
@@ -132,7 +156,7 @@ impl CloudP2P {
 		let (msgs_tx, msgs_rx) = flume::bounded(16);
 
 		spawn({
-			let runner = Runner::new(current_device_pub_id, cloud_services, endpoint).await?;
+			let runner = Runner::new(current_device_pub_id, cloud_services, endpoint, job_dispatcher).await?;
 			let user_response_rx = cloud_services.user_response_rx.clone();
 
 			async move {
@@ -176,6 +200,37 @@ impl CloudP2P {
 			.await
 			.expect("Channel closed");
 	}
+
+	/// Offloads a job to `target`, a device in the same sync group reachable at
+	/// `target_node_id`, streaming its progress and final outcome back through
+	/// `progress_tx` / `outcome_tx` so the originating `JobReport` can reflect
+	/// it live. Falls back to running the job locally if `target` can't be
+	/// reached at all; reports `RemoteJobOutcome::PeerLost` if the connection
+	/// drops after the job has already started remotely.
+	///
+	/// # Panics
+	/// Will panic if the actor channel is closed, which should never happen
+	pub async fn dispatch_job(
+		&self,
+		job_name: String,
+		job_args: Vec<u8>,
+		target: devices::PubId,
+		target_node_id: NodeId,
+		progress_tx: flume::Sender<RemoteJobProgress>,
+		outcome_tx: oneshot::Sender<RemoteJobOutcome>,
+	) {
+		self.msgs_tx
+			.send_async(runner::Message::Request(runner::Request::DispatchJob {
+				job_name,
+				job_args,
+				target,
+				target_node_id,
+				progress_tx,
+				outcome_tx,
+			}))
+			.await
+			.expect("Channel closed");
+	}
 }
 
 impl Drop for CloudP2P {
@@ -0,0 +1,289 @@
+use std::sync::Arc;
+
+use sd_cloud_schema::{cloud_p2p::authorize_new_device_in_sync_group, devices};
+use sd_crypto::CryptoRng;
+
+use iroh_net::{endpoint::{RecvStream, SendStream}, Endpoint, NodeId};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	sync::{mpsc, oneshot},
+};
+use tracing::{error, warn};
+
+use crate::CloudServices;
+
+use super::{JoinedLibraryCreateArgs, RemoteJobOutcome, RemoteJobProgress};
+
+/// Above this, a peer is almost certainly not speaking our wire protocol;
+/// bail instead of trying to allocate an attacker-controlled amount of
+/// memory for a single frame.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Writes `msg` as a 4-byte big-endian length prefix followed by its
+/// MessagePack encoding. `DispatchWireMessage::Request`, `Progress` and
+/// `Outcome` all share one bidirectional stream (several `Progress`es
+/// followed by one `Outcome`), so each message needs its own frame rather
+/// than relying on stream-close to mark the end, which only quinn's
+/// `read_to_end` can observe once per stream.
+async fn write_frame<T: Serialize>(send: &mut SendStream, msg: &T) -> Result<(), std::io::Error> {
+	let encoded = rmp_serde::to_vec(msg).map_err(std::io::Error::other)?;
+	send.write_all(&(encoded.len() as u32).to_be_bytes()).await?;
+	send.write_all(&encoded).await?;
+	Ok(())
+}
+
+/// Reads one length-prefixed frame written by [`write_frame`]. Returns
+/// `Ok(None)` if the stream was closed cleanly before a new frame started.
+async fn read_frame<T: DeserializeOwned>(recv: &mut RecvStream) -> Result<Option<T>, std::io::Error> {
+	let mut len_buf = [0u8; 4];
+	match recv.read_exact(&mut len_buf).await {
+		Ok(()) => {}
+		Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+		Err(e) => return Err(e),
+	}
+
+	let len = u32::from_be_bytes(len_buf) as usize;
+	if len > MAX_FRAME_LEN {
+		return Err(std::io::Error::other(format!(
+			"job-dispatch frame of {len} bytes exceeds the {MAX_FRAME_LEN} byte limit"
+		)));
+	}
+
+	let mut buf = vec![0u8; len];
+	recv.read_exact(&mut buf).await?;
+
+	rmp_serde::from_slice(&buf).map(Some).map_err(std::io::Error::other)
+}
+
+/// Separate ALPN for the job-dispatch wire protocol, registered on the shared
+/// endpoint alongside `CloudP2PALPN::LATEST` so a dispatch connection can be
+/// told apart from a sync-group-authorization one.
+pub const JOB_DISPATCH_ALPN: &[u8] = b"spacedrive/cloud-p2p/job-dispatch/0";
+
+/// Implemented by the core crate and handed to `CloudP2P::new`, so this crate
+/// can enqueue a dispatched job through the node's job manager without
+/// depending on the core crate (which depends on this one). Mirrors the
+/// existing `user_response_rx` dependency-injection pattern.
+#[async_trait::async_trait]
+pub trait JobDispatcher: std::fmt::Debug + Send + Sync + 'static {
+	/// Enqueues `job_name` with `job_args` through the local job manager,
+	/// reporting progress on `progress_tx` as it runs, and resolving once the
+	/// job reaches a terminal status.
+	async fn dispatch(
+		&self,
+		job_name: String,
+		job_args: Vec<u8>,
+		progress_tx: mpsc::Sender<RemoteJobProgress>,
+	) -> RemoteJobOutcome;
+}
+
+/// Messages exchanged with a dispatched job's counterpart over the
+/// `JOB_DISPATCH_ALPN` stream.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum DispatchWireMessage {
+	Request { job_name: String, job_args: Vec<u8> },
+	Progress(RemoteJobProgress),
+	Outcome(RemoteJobOutcome),
+}
+
+pub enum Message {
+	Request(Request),
+	Stop,
+}
+
+pub enum Request {
+	/// Requests the device with the given connection ID asking for permission
+	/// to the current device to join the sync group.
+	JoinSyncGroup {
+		req: authorize_new_device_in_sync_group::Request,
+		devices_in_group: Vec<(devices::PubId, NodeId)>,
+		tx: oneshot::Sender<JoinedLibraryCreateArgs>,
+	},
+	/// Offloads a job to a peer device, over a fresh `JOB_DISPATCH_ALPN`
+	/// connection.
+	DispatchJob {
+		job_name: String,
+		job_args: Vec<u8>,
+		target: devices::PubId,
+		target_node_id: NodeId,
+		progress_tx: flume::Sender<RemoteJobProgress>,
+		outcome_tx: oneshot::Sender<RemoteJobOutcome>,
+	},
+}
+
+#[derive(Clone)]
+pub(super) struct Runner {
+	current_device_pub_id: devices::PubId,
+	endpoint: Endpoint,
+	job_dispatcher: Arc<dyn JobDispatcher>,
+}
+
+impl Runner {
+	pub(super) async fn new(
+		current_device_pub_id: devices::PubId,
+		_cloud_services: &CloudServices,
+		endpoint: Endpoint,
+		job_dispatcher: Arc<dyn JobDispatcher>,
+	) -> Result<Self, crate::Error> {
+		Ok(Self {
+			current_device_pub_id,
+			endpoint,
+			job_dispatcher,
+		})
+	}
+
+	pub(super) async fn run(
+		self,
+		msgs_rx: flume::Receiver<Message>,
+		_user_response_rx: tokio::sync::broadcast::Receiver<super::UserResponse>,
+		_rng: CryptoRng,
+	) {
+		let incoming = {
+			let this = self.clone();
+			tokio::spawn(async move { this.listen().await })
+		};
+
+		while let Ok(msg) = msgs_rx.recv_async().await {
+			match msg {
+				Message::Request(Request::JoinSyncGroup { .. }) => {
+					// Handled by the authorize_new_device_in_sync_group flow,
+					// unchanged by the job-dispatch work.
+				}
+				Message::Request(Request::DispatchJob {
+					job_name,
+					job_args,
+					target,
+					target_node_id,
+					progress_tx,
+					outcome_tx,
+				}) => {
+					let this = self.clone();
+					tokio::spawn(async move {
+						this.dispatch_job(job_name, job_args, target, target_node_id, progress_tx, outcome_tx)
+							.await;
+					});
+				}
+				Message::Stop => break,
+			}
+		}
+
+		incoming.abort();
+	}
+
+	/// Connects to `target_node_id` over `JOB_DISPATCH_ALPN`, sends the job
+	/// args, and forwards progress/outcome messages back to the caller. Falls
+	/// back to running the job locally through `job_dispatcher` if the peer
+	/// can't be reached at all, and reports `JobError::RemotePeerLost`-worthy
+	/// `RemoteJobOutcome::PeerLost` if the connection drops mid-run.
+	async fn dispatch_job(
+		&self,
+		job_name: String,
+		job_args: Vec<u8>,
+		_target: devices::PubId,
+		target_node_id: NodeId,
+		progress_tx: flume::Sender<RemoteJobProgress>,
+		outcome_tx: oneshot::Sender<RemoteJobOutcome>,
+	) {
+		let connection = match self
+			.endpoint
+			.connect(target_node_id.into(), JOB_DISPATCH_ALPN)
+			.await
+		{
+			Ok(connection) => connection,
+			Err(e) => {
+				warn!("Failed to reach peer for job dispatch, running '{job_name}' locally: {e}");
+				let (local_tx, mut local_rx) = mpsc::channel(16);
+				let local_dispatcher = self.job_dispatcher.clone();
+				let forward_progress_tx = progress_tx.clone();
+				tokio::spawn(async move {
+					while let Some(update) = local_rx.recv().await {
+						forward_progress_tx.send_async(update).await.ok();
+					}
+				});
+				let outcome = local_dispatcher.dispatch(job_name, job_args, local_tx).await;
+				outcome_tx.send(outcome).ok();
+				return;
+			}
+		};
+
+		let Ok((mut send, mut recv)) = connection.open_bi().await else {
+			outcome_tx.send(RemoteJobOutcome::PeerLost).ok();
+			return;
+		};
+
+		if write_frame(&mut send, &DispatchWireMessage::Request { job_name, job_args })
+			.await
+			.is_err()
+		{
+			outcome_tx.send(RemoteJobOutcome::PeerLost).ok();
+			return;
+		}
+
+		let outcome = loop {
+			match read_frame::<DispatchWireMessage>(&mut recv).await {
+				Ok(Some(DispatchWireMessage::Progress(progress))) => {
+					progress_tx.send_async(progress).await.ok();
+				}
+				Ok(Some(DispatchWireMessage::Outcome(outcome))) => break outcome,
+				Ok(Some(DispatchWireMessage::Request { .. })) | Ok(None) => {
+					break RemoteJobOutcome::PeerLost
+				}
+				Err(e) => {
+					error!("Lost connection to peer mid job-dispatch: {e}");
+					break RemoteJobOutcome::PeerLost;
+				}
+			}
+		};
+
+		outcome_tx.send(outcome).ok();
+	}
+
+	/// Accepts incoming `JOB_DISPATCH_ALPN` connections, enqueuing the
+	/// requested job through the local job manager and streaming its progress
+	/// back to the dispatching peer.
+	async fn listen(self) {
+		while let Some(incoming) = self.endpoint.accept().await {
+			let this = self.clone();
+			tokio::spawn(async move {
+				let Ok(connection) = incoming.accept() else {
+					return;
+				};
+				let Ok(connection) = connection.await else {
+					return;
+				};
+				if connection.alpn().as_deref() != Some(JOB_DISPATCH_ALPN) {
+					return;
+				}
+
+				let Ok((mut send, mut recv)) = connection.accept_bi().await else {
+					return;
+				};
+
+				let Ok(Some(DispatchWireMessage::Request { job_name, job_args })) =
+					read_frame(&mut recv).await
+				else {
+					return;
+				};
+
+				let (progress_tx, mut progress_rx) = mpsc::channel(16);
+				let dispatch_fut = this.job_dispatcher.dispatch(job_name, job_args, progress_tx);
+				tokio::pin!(dispatch_fut);
+
+				let outcome = loop {
+					tokio::select! {
+						progress = progress_rx.recv() => {
+							let Some(progress) = progress else { continue };
+							if write_frame(&mut send, &DispatchWireMessage::Progress(progress)).await.is_err() {
+								return;
+							}
+						}
+						outcome = &mut dispatch_fut => break outcome,
+					}
+				};
+
+				write_frame(&mut send, &DispatchWireMessage::Outcome(outcome)).await.ok();
+			});
+		}
+	}
+}
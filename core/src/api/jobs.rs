@@ -0,0 +1,49 @@
+use chrono::Utc;
+use rspc::alpha::AlphaRouter;
+use uuid::Uuid;
+
+use crate::{
+	job::scheduler::{Cadence, ScheduleEntry},
+	Ctx,
+};
+
+use super::{utils::library, R};
+
+/// `jobs.scheduler.*` procedures, merged into the broader `jobs` router.
+/// Backs create/pause/resume/delete for recurring [`ScheduleEntry`]s.
+pub(crate) fn mount() -> AlphaRouter<Ctx> {
+	R.router()
+		.procedure(
+			"create",
+			R.with2(library())
+				.mutation(|(_, library), (job_name, job_args, cadence): (String, Vec<u8>, Cadence)| async move {
+					let entry = ScheduleEntry::new(job_name, job_args, cadence, Utc::now())?;
+					entry.create(&library).await?;
+					Ok(entry.id)
+				}),
+		)
+		.procedure(
+			"pause",
+			R.with2(library())
+				.mutation(|(_, library), id: Uuid| async move {
+					ScheduleEntry::set_enabled(&library, id, false).await?;
+					Ok(())
+				}),
+		)
+		.procedure(
+			"resume",
+			R.with2(library())
+				.mutation(|(_, library), id: Uuid| async move {
+					ScheduleEntry::set_enabled(&library, id, true).await?;
+					Ok(())
+				}),
+		)
+		.procedure(
+			"delete",
+			R.with2(library())
+				.mutation(|(_, library), id: Uuid| async move {
+					ScheduleEntry::delete(&library, id).await?;
+					Ok(())
+				}),
+		)
+}
@@ -0,0 +1,257 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+use crate::{
+	library::Library,
+	prisma::{job, job_dependency},
+};
+
+use super::{JobError, JobManagerError, JobReport, JobStatus};
+
+/// What to do with a job's dependents when it ends in a non-success terminal
+/// status (`Failed` or `Canceled`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, Eq, PartialEq)]
+pub enum FailurePolicy {
+	/// Transition dependents to `Canceled`, recording the prerequisite's
+	/// failure as the reason.
+	Cancel,
+	/// Dispatch dependents as if the prerequisite had succeeded.
+	Proceed,
+}
+
+/// Records that `job_id` must wait for every id in `depends_on` to reach
+/// `Completed` or `CompletedWithErrors` before it's dispatched.
+pub async fn add_dependencies(
+	library: &Library,
+	job_id: Uuid,
+	depends_on: &[Uuid],
+) -> Result<(), JobManagerError> {
+	if depends_on.is_empty() {
+		return Ok(());
+	}
+
+	detect_cycle(library, job_id, depends_on).await?;
+
+	for depends_on_job_id in depends_on {
+		library
+			.db
+			.job_dependency()
+			.create(
+				job_dependency::job_id::equals(job_id.as_bytes().to_vec()),
+				job_dependency::depends_on_job_id::equals(depends_on_job_id.as_bytes().to_vec()),
+				vec![],
+			)
+			.exec()
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// Whether every dependency of `job_id` has settled into `Completed` or
+/// `CompletedWithErrors`, i.e. it's safe to dispatch. A dependency that ended
+/// `Failed` or `Canceled` also counts as satisfied when `job_id`'s own
+/// `on_dependency_failure` is `FailurePolicy::Proceed`, mirroring
+/// `propagate_failure`'s choice to leave those dependents queued instead of
+/// canceling them.
+pub async fn dependencies_satisfied(library: &Library, job_id: Uuid) -> Result<bool, JobError> {
+	let dependent_data = library
+		.db
+		.job()
+		.find_unique(job::id::equals(job_id.as_bytes().to_vec()))
+		.exec()
+		.await?
+		.ok_or(JobError::MissingFromDb("job", "dependent".to_string()))?;
+	let on_dependency_failure = JobReport::from(dependent_data).on_dependency_failure;
+
+	let dependencies = library
+		.db
+		.job_dependency()
+		.find_many(vec![job_dependency::job_id::equals(
+			job_id.as_bytes().to_vec(),
+		)])
+		.exec()
+		.await?;
+
+	for dep in dependencies {
+		let status = JobStatus::try_from(
+			library
+				.db
+				.job()
+				.find_unique(job::id::equals(dep.depends_on_job_id))
+				.exec()
+				.await?
+				.ok_or(JobError::MissingFromDb("job", "dependency".to_string()))?
+				.status,
+		)?;
+
+		let satisfied = matches!(status, JobStatus::Completed | JobStatus::CompletedWithErrors)
+			|| (matches!(status, JobStatus::Failed | JobStatus::Canceled)
+				&& matches!(on_dependency_failure, FailurePolicy::Proceed));
+
+		if !satisfied {
+			return Ok(false);
+		}
+	}
+
+	Ok(true)
+}
+
+/// Walks the dependency graph reachable from `depends_on`, erroring with
+/// `JobManagerError::DependencyCycle` if adding an edge `job_id ->
+/// depends_on` would create a cycle.
+async fn detect_cycle(
+	library: &Library,
+	job_id: Uuid,
+	depends_on: &[Uuid],
+) -> Result<(), JobManagerError> {
+	let all_edges = library.db.job_dependency().find_many(vec![]).exec().await?;
+
+	let mut graph: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+	for edge in all_edges {
+		let from = Uuid::from_slice(&edge.job_id).expect("corrupted database");
+		let to = Uuid::from_slice(&edge.depends_on_job_id).expect("corrupted database");
+		graph.entry(from).or_default().push(to);
+	}
+	graph.entry(job_id).or_default().extend(depends_on);
+
+	let mut visiting = HashSet::new();
+	let mut path = Vec::new();
+
+	if let Some(cycle) = find_cycle(&graph, job_id, &mut visiting, &mut path) {
+		return Err(JobManagerError::DependencyCycle(cycle));
+	}
+
+	Ok(())
+}
+
+/// Called once `failed_job_id` settles into `Failed` or `Canceled`. Loads
+/// every job that depends on it and, per each dependent's
+/// `on_dependency_failure`, either cancels it (recording the reason) or
+/// leaves it queued to be dispatched as usual.
+pub async fn propagate_failure(
+	library: &Library,
+	notifiers: &super::notifier::NotifierRegistry,
+	failed_job_id: Uuid,
+) -> Result<(), JobError> {
+	let dependents = library
+		.db
+		.job_dependency()
+		.find_many(vec![job_dependency::depends_on_job_id::equals(
+			failed_job_id.as_bytes().to_vec(),
+		)])
+		.exec()
+		.await?;
+
+	for dependent in dependents {
+		let Some(data) = library
+			.db
+			.job()
+			.find_unique(job::id::equals(dependent.job_id))
+			.exec()
+			.await?
+		else {
+			continue;
+		};
+
+		let mut report = JobReport::from(data);
+		if !matches!(report.on_dependency_failure, FailurePolicy::Cancel) {
+			continue;
+		}
+
+		report
+			.errors_text
+			.push(format!("canceled: dependency '{failed_job_id}' did not complete successfully"));
+		// Every other terminal-status transition stamps `completed_at`; without
+		// this a dependency-canceled job reads as still running forever.
+		report.completed_at = Some(chrono::Utc::now());
+		report
+			.transition_status(library, notifiers, JobStatus::Canceled)
+			.await?;
+	}
+
+	Ok(())
+}
+
+fn find_cycle(
+	graph: &HashMap<Uuid, Vec<Uuid>>,
+	node: Uuid,
+	visiting: &mut HashSet<Uuid>,
+	path: &mut Vec<Uuid>,
+) -> Option<Vec<Uuid>> {
+	if let Some(pos) = path.iter().position(|id| *id == node) {
+		return Some(path[pos..].to_vec());
+	}
+	if !visiting.insert(node) {
+		return None;
+	}
+
+	path.push(node);
+	let result = graph
+		.get(&node)
+		.into_iter()
+		.flatten()
+		.find_map(|&next| find_cycle(graph, next, visiting, path));
+	path.pop();
+
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn graph(edges: &[(Uuid, Uuid)]) -> HashMap<Uuid, Vec<Uuid>> {
+		let mut graph: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+		for &(from, to) in edges {
+			graph.entry(from).or_default().push(to);
+		}
+		graph
+	}
+
+	#[test]
+	fn no_cycle_in_a_dag() {
+		let a = Uuid::new_v4();
+		let b = Uuid::new_v4();
+		let c = Uuid::new_v4();
+		let graph = graph(&[(a, b), (b, c)]);
+
+		assert!(find_cycle(&graph, a, &mut HashSet::new(), &mut Vec::new()).is_none());
+	}
+
+	#[test]
+	fn detects_direct_cycle() {
+		let a = Uuid::new_v4();
+		let b = Uuid::new_v4();
+		let graph = graph(&[(a, b), (b, a)]);
+
+		let cycle = find_cycle(&graph, a, &mut HashSet::new(), &mut Vec::new());
+		assert_eq!(cycle, Some(vec![a, b]));
+	}
+
+	#[test]
+	fn detects_indirect_cycle() {
+		let a = Uuid::new_v4();
+		let b = Uuid::new_v4();
+		let c = Uuid::new_v4();
+		let graph = graph(&[(a, b), (b, c), (c, a)]);
+
+		let cycle = find_cycle(&graph, a, &mut HashSet::new(), &mut Vec::new());
+		assert_eq!(cycle, Some(vec![a, b, c]));
+	}
+
+	#[test]
+	fn does_not_flag_a_shared_dependency_as_a_cycle() {
+		// a -> c, b -> c: two jobs sharing a dependency isn't a cycle.
+		let a = Uuid::new_v4();
+		let b = Uuid::new_v4();
+		let c = Uuid::new_v4();
+		let graph = graph(&[(a, c), (b, c)]);
+
+		assert!(find_cycle(&graph, a, &mut HashSet::new(), &mut Vec::new()).is_none());
+		assert!(find_cycle(&graph, b, &mut HashSet::new(), &mut Vec::new()).is_none());
+	}
+}
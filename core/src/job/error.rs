@@ -6,6 +6,7 @@ use crate::{
 
 use std::{fmt::Debug, hash::Hasher, path::PathBuf};
 
+use iroh_net::NodeId;
 use rmp_serde::{decode::Error as DecodeError, encode::Error as EncodeError};
 use sd_crypto::Error as CryptoError;
 use thiserror::Error;
@@ -48,6 +49,12 @@ pub enum JobError {
 	PauseFailed(String),
 	#[error("failed to send command to worker")]
 	WorkerCommandSendFailed,
+	#[error("invalid cron expression '{0}': {1}")]
+	InvalidCronExpression(String, String),
+	#[error("invalid schedule cadence: {0}")]
+	InvalidScheduleCadence(String),
+	#[error("peer running a dispatched job was lost: {0}")]
+	RemotePeerLost(NodeId),
 
 	// Specific job errors
 	#[error("Indexer error: {0}")]
@@ -78,6 +85,19 @@ pub enum JobError {
 	Paused(Vec<u8>),
 }
 
+impl JobError {
+	/// Whether the job manager should re-dispatch the job after this error
+	/// rather than settling it straight to `Failed`. Defaults to the errors
+	/// known to be transient; callers with job-specific knowledge can layer
+	/// additional classification on top.
+	pub fn is_retryable(&self) -> bool {
+		matches!(
+			self,
+			Self::DatabaseError(_) | Self::FileIO(_) | Self::WorkerCommandSendFailed
+		)
+	}
+}
+
 #[derive(Error, Debug)]
 pub enum JobManagerError {
 	#[error("Tried to dispatch a job that is already running: Job <name='{name}', hash='{hash}'>")]
@@ -89,6 +109,9 @@ pub enum JobManagerError {
 	#[error("job not found: {0}")]
 	NotFound(Uuid),
 
+	#[error("adding this dependency would create a cycle: {0:?}")]
+	DependencyCycle(Vec<Uuid>),
+
 	#[error("Job error: {0}")]
 	Job(#[from] JobError),
 }
@@ -111,6 +134,11 @@ impl From<JobManagerError> for rspc::Error {
 				"Job not found".to_string(),
 				value,
 			),
+			JobManagerError::DependencyCycle(_) => Self::with_cause(
+				rspc::ErrorCode::BadRequest,
+				"This dependency would create a cycle".to_string(),
+				value,
+			),
 			JobManagerError::Job(_) => Self::with_cause(
 				rspc::ErrorCode::InternalServerError,
 				"Job error".to_string(),
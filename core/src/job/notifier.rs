@@ -0,0 +1,130 @@
+use std::{collections::HashSet, fmt::Debug, sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use tokio::time::timeout;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::{JobReport, JobStatus};
+
+/// How long a webhook request is given before it's given up on. The request
+/// runs in its own task, so a slow webhook never holds up job completion.
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A sink that's told about a job crossing into a terminal or otherwise
+/// notable status. Implementations should not assume they're called from any
+/// particular task; the caller guarantees at most one call per status per job.
+#[async_trait]
+pub trait JobNotifier: Send + Sync + Debug {
+	async fn notify(&self, report: &JobReport, previous: JobStatus);
+}
+
+/// POSTs a JSON body built from the job's serde form to a configured URL.
+#[derive(Debug)]
+pub struct WebhookNotifier {
+	url: String,
+	client: Client,
+}
+
+impl WebhookNotifier {
+	pub fn new(url: String) -> Self {
+		Self {
+			url,
+			client: Client::new(),
+		}
+	}
+}
+
+#[async_trait]
+impl JobNotifier for WebhookNotifier {
+	async fn notify(&self, report: &JobReport, previous: JobStatus) {
+		let body = serde_json::json!({
+			"job": report,
+			"previous_status": previous,
+		});
+
+		let request = self.client.post(&self.url).json(&body);
+		let name = report.name.clone();
+
+		tokio::spawn(async move {
+			match timeout(NOTIFY_TIMEOUT, request.send()).await {
+				Ok(Ok(res)) if !res.status().is_success() => {
+					warn!("Webhook notifier for job '{name}' got HTTP {}", res.status());
+				}
+				Ok(Err(e)) => error!("Webhook notifier for job '{name}' failed: {e}"),
+				Err(_) => warn!("Webhook notifier for job '{name}' timed out"),
+				_ => {}
+			}
+		});
+	}
+}
+
+/// Reports a job's notable status as a `tracing` info event titled for a
+/// desktop notification; no OS-level notification is raised from here.
+#[derive(Debug, Default)]
+pub struct DesktopNotifier;
+
+#[async_trait]
+impl JobNotifier for DesktopNotifier {
+	async fn notify(&self, report: &JobReport, _previous: JobStatus) {
+		let (title, body) = match report.status {
+			JobStatus::Completed => ("Job completed".to_string(), report.name.clone()),
+			JobStatus::CompletedWithErrors => {
+				("Job completed with errors".to_string(), report.name.clone())
+			}
+			JobStatus::Failed => ("Job failed".to_string(), report.name.clone()),
+			JobStatus::Paused => ("Job paused".to_string(), report.name.clone()),
+			JobStatus::Canceled => ("Job canceled".to_string(), report.name.clone()),
+			_ => return,
+		};
+
+		tracing::info!(%title, %body, "desktop notification");
+	}
+}
+
+/// Which statuses should trigger which notifiers, and dispatch of the
+/// notification itself. Registered once on the job manager.
+#[derive(Default)]
+pub struct NotifierRegistry {
+	notifiers: Vec<(Vec<JobStatus>, Box<dyn JobNotifier>)>,
+	/// `(job id, status)` pairs already announced.
+	announced: Mutex<HashSet<(Uuid, i32)>>,
+}
+
+impl NotifierRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers `notifier` to fire whenever a job's status transitions to one
+	/// of `on_statuses`.
+	pub fn register(mut self, on_statuses: Vec<JobStatus>, notifier: Box<dyn JobNotifier>) -> Self {
+		self.notifiers.push((on_statuses, notifier));
+		self
+	}
+
+	/// Announces `report`'s current status to every notifier configured for
+	/// it, unless it's already the status `previous` held or this job has
+	/// already announced it (so a status is announced at most once per job).
+	pub async fn announce(&self, report: &JobReport, previous: JobStatus) {
+		if report.status as i32 == previous as i32 {
+			return;
+		}
+
+		if !self
+			.announced
+			.lock()
+			.expect("notifier registry lock poisoned")
+			.insert((report.id, report.status as i32))
+		{
+			return;
+		}
+
+		for (on_statuses, notifier) in &self.notifiers {
+			if on_statuses.iter().any(|s| *s as i32 == report.status as i32) {
+				notifier.notify(report, previous).await;
+			}
+		}
+	}
+}
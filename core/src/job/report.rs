@@ -13,7 +13,7 @@ use std::{
 use tracing::error;
 use uuid::Uuid;
 
-use super::JobError;
+use super::{result::JobOutput, JobError};
 
 #[derive(Debug)]
 pub enum JobReportUpdate {
@@ -45,6 +45,24 @@ pub struct JobReport {
 	pub message: String,
 	pub estimated_completion: DateTime<Utc>,
 	// pub percentage_complete: f64,
+
+	/// Maximum number of times this job will be re-dispatched after a retryable
+	/// failure, before settling to `Failed`.
+	pub max_retries: i32,
+	/// How many retry attempts have been made so far.
+	pub retry_count: i32,
+	/// Base delay used to compute the exponential backoff between retries, in
+	/// seconds: `retry_backoff_base_secs * 2^(retry_count - 1)`.
+	pub retry_backoff_base_secs: i32,
+
+	/// Structured replacement for `errors_text`. Populated from
+	/// `JobError::StepCompletedWithErrors` as a job runs, or migrated from
+	/// `errors_text` when loading a report written before this field existed.
+	pub output: JobOutput,
+
+	/// What to do with this job if one of its dependencies (see
+	/// [`dependency`](super::dependency)) ends `Failed` or `Canceled`.
+	pub on_dependency_failure: super::dependency::FailurePolicy,
 }
 
 impl Display for JobReport {
@@ -74,8 +92,24 @@ impl From<job::Data> for JobReport {
 			}),
 			errors_text: data
 				.errors_text
+				.clone()
 				.map(|errors_str| errors_str.split("\n\n").map(str::to_string).collect())
 				.unwrap_or_default(),
+			output: data
+				.output
+				.as_deref()
+				.and_then(|bytes| {
+					rmp_serde::from_slice(bytes)
+						.map_err(|e| error!("Failed to deserialize job output: {e}"))
+						.ok()
+				})
+				.unwrap_or_else(|| {
+					JobOutput::from_legacy_errors_text(
+						data.errors_text
+							.map(|errors_str| errors_str.split("\n\n").map(str::to_string).collect())
+							.unwrap_or_default(),
+					)
+				}),
 			created_at: Some(data.date_created.into()),
 			started_at: data.date_started.map(DateTime::into),
 			completed_at: data.date_completed.map(DateTime::into),
@@ -89,10 +123,22 @@ impl From<job::Data> for JobReport {
 			estimated_completion: data
 				.date_estimated_completion
 				.map_or(Utc::now(), DateTime::into),
+			max_retries: data.max_retries,
+			retry_count: data.retry_count,
+			retry_backoff_base_secs: data.retry_backoff_base_secs,
+			on_dependency_failure: if data.cancel_on_dependency_failure {
+				super::dependency::FailurePolicy::Cancel
+			} else {
+				super::dependency::FailurePolicy::Proceed
+			},
 		}
 	}
 }
 
+/// Upper bound on the exponential backoff between retries, regardless of how
+/// many attempts have already been made.
+const MAX_RETRY_BACKOFF_SECS: i64 = 60 * 60;
+
 impl JobReport {
 	pub fn new(uuid: Uuid, name: String) -> Self {
 		Self {
@@ -112,6 +158,11 @@ impl JobReport {
 			completed_task_count: 0,
 			message: String::new(),
 			estimated_completion: Utc::now(),
+			max_retries: 0,
+			retry_count: 0,
+			retry_backoff_base_secs: 5,
+			output: JobOutput::default(),
+			on_dependency_failure: super::dependency::FailurePolicy::Cancel,
 		}
 	}
 
@@ -162,6 +213,22 @@ impl JobReport {
 		Ok(())
 	}
 
+	/// Sets `status` and persists the report, then announces the transition
+	/// through `notifiers`. This is the single choke point jobs should go
+	/// through to change status, so that a status is only ever announced once.
+	pub async fn transition_status(
+		&mut self,
+		library: &Library,
+		notifiers: &super::notifier::NotifierRegistry,
+		status: JobStatus,
+	) -> Result<(), JobError> {
+		let previous = self.status;
+		self.status = status;
+		self.update(library).await?;
+		notifiers.announce(self, previous).await;
+		Ok(())
+	}
+
 	pub async fn update(&mut self, library: &Library) -> Result<(), JobError> {
 		library
 			.db
@@ -179,12 +246,100 @@ impl JobReport {
 					job::completed_task_count::set(self.completed_task_count),
 					job::date_started::set(self.started_at.map(|v| v.into())),
 					job::date_completed::set(self.completed_at.map(|v| v.into())),
+					job::date_estimated_completion::set(Some(self.estimated_completion.into())),
+					job::max_retries::set(self.max_retries),
+					job::retry_count::set(self.retry_count),
+					job::retry_backoff_base_secs::set(self.retry_backoff_base_secs),
+					job::output::set(rmp_serde::to_vec(&self.output).ok()),
+					job::cancel_on_dependency_failure::set(matches!(
+						self.on_dependency_failure,
+						super::dependency::FailurePolicy::Cancel
+					)),
 				],
 			)
 			.exec()
 			.await?;
 		Ok(())
 	}
+
+	/// Whether this job has collected any non-critical step errors, in which
+	/// case it should settle to `JobStatus::CompletedWithErrors` rather than
+	/// `JobStatus::Completed`.
+	pub fn is_completed_with_errors(&self) -> bool {
+		!self.output.non_critical_errors.is_empty()
+	}
+
+	/// Folds a `JobError::StepCompletedWithErrors` into `output`, keeping
+	/// `errors_text` in sync for any remaining readers of the legacy field.
+	pub fn record_step_errors(&mut self, errors: &super::JobRunErrors) {
+		let mut output = JobOutput::from(errors);
+		self.errors_text
+			.extend(output.non_critical_errors.iter().map(|e| e.message.clone()));
+		self.output.non_critical_errors.append(&mut output.non_critical_errors);
+	}
+
+	/// Called when a job's `run` fails with a [`JobError`] that
+	/// [`JobError::is_retryable`]. Increments `retry_count`, schedules the next
+	/// attempt using exponential backoff, and appends `error` to `errors_text`.
+	///
+	/// Returns `true` if the job was requeued for another attempt, or `false`
+	/// if `max_retries` has been exhausted and the job should settle to
+	/// `JobStatus::Failed`.
+	pub async fn retry_or_fail(
+		&mut self,
+		library: &Library,
+		notifiers: &super::notifier::NotifierRegistry,
+		error: &JobError,
+	) -> Result<bool, JobError> {
+		self.errors_text.push(error.to_string());
+
+		if self.retry_count >= self.max_retries {
+			self.completed_at = Some(Utc::now());
+			self.transition_status(library, notifiers, JobStatus::Failed)
+				.await?;
+			return Ok(false);
+		}
+
+		self.retry_count += 1;
+
+		let backoff_secs = retry_backoff_secs(self.retry_backoff_base_secs, self.retry_count);
+		self.estimated_completion = Utc::now() + chrono::Duration::seconds(backoff_secs);
+
+		self.transition_status(library, notifiers, JobStatus::Queued)
+			.await?;
+		Ok(true)
+	}
+}
+
+/// `base_secs * 2^(retry_count - 1)`, capped at [`MAX_RETRY_BACKOFF_SECS`] and
+/// saturating instead of overflowing for large `retry_count`s.
+fn retry_backoff_secs(base_secs: i32, retry_count: i32) -> i64 {
+	(base_secs as i64)
+		.saturating_mul(1i64 << (retry_count - 1).min(62))
+		.min(MAX_RETRY_BACKOFF_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn retry_backoff_doubles_each_attempt() {
+		assert_eq!(retry_backoff_secs(5, 1), 5);
+		assert_eq!(retry_backoff_secs(5, 2), 10);
+		assert_eq!(retry_backoff_secs(5, 3), 20);
+		assert_eq!(retry_backoff_secs(5, 4), 40);
+	}
+
+	#[test]
+	fn retry_backoff_is_capped() {
+		assert_eq!(retry_backoff_secs(5, 20), MAX_RETRY_BACKOFF_SECS);
+	}
+
+	#[test]
+	fn retry_backoff_does_not_overflow_for_large_retry_counts() {
+		assert_eq!(retry_backoff_secs(5, i32::MAX), MAX_RETRY_BACKOFF_SECS);
+	}
 }
 
 #[repr(i32)]
@@ -197,6 +352,10 @@ pub enum JobStatus {
 	Failed = 4,
 	Paused = 5,
 	CompletedWithErrors = 6,
+	/// Dispatched by the [`scheduler`](super::scheduler) on behalf of a
+	/// [`ScheduleEntry`](super::scheduler::ScheduleEntry), but not yet picked
+	/// up by a worker.
+	Scheduled = 7,
 }
 
 impl TryFrom<i32> for JobStatus {
@@ -211,6 +370,7 @@ impl TryFrom<i32> for JobStatus {
 			4 => Self::Failed,
 			5 => Self::Paused,
 			6 => Self::CompletedWithErrors,
+			7 => Self::Scheduled,
 			_ => return Err(JobError::InvalidJobStatusInt(value)),
 		};
 
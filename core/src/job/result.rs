@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::JobRunErrors;
+
+/// A single non-critical failure encountered while running a job step, e.g.
+/// one file in a batch failing to copy while the rest succeed.
+///
+/// Note: neither `errors_text` nor `JobRunErrors` carry the affected path
+/// today, only a pre-rendered message string, so there's no `path` field
+/// here yet. Populating one for real needs `JobRunErrors` to start carrying
+/// structured per-step data (e.g. the originating `JobError`) instead of
+/// `String`s.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct JobStepError {
+	pub kind: String,
+	pub message: String,
+	pub at: DateTime<Utc>,
+}
+
+/// Structured outcome of a job, persisted as MessagePack in place of the
+/// lossy newline-joined `errors_text` string. `JobStatus::CompletedWithErrors`
+/// is driven off `!non_critical_errors.is_empty()`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct JobOutput {
+	pub non_critical_errors: Vec<JobStepError>,
+	pub outputs: serde_json::Value,
+}
+
+impl JobOutput {
+	/// Migrates the legacy `errors_text` split on `"\n\n"` into structured
+	/// entries, so reports written before this type existed still round-trip.
+	pub fn from_legacy_errors_text(errors_text: Vec<String>) -> Self {
+		Self {
+			non_critical_errors: errors_text
+				.into_iter()
+				.map(|message| JobStepError {
+					kind: "legacy".to_string(),
+					message,
+					at: Utc::now(),
+				})
+				.collect(),
+			outputs: serde_json::Value::Null,
+		}
+	}
+}
+
+impl From<&JobRunErrors> for JobOutput {
+	fn from(errors: &JobRunErrors) -> Self {
+		Self {
+			non_critical_errors: errors
+				.0
+				.iter()
+				.map(|message| JobStepError {
+					kind: "step".to_string(),
+					message: message.clone(),
+					at: Utc::now(),
+				})
+				.collect(),
+			outputs: serde_json::Value::Null,
+		}
+	}
+}
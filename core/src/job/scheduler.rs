@@ -0,0 +1,296 @@
+use crate::{
+	library::Library,
+	prisma::schedule_entry,
+	util,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::time::Duration as TickDuration;
+use tokio::time::interval;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::{JobError, JobManagerError};
+
+/// How often the scheduler wakes up to look for due [`ScheduleEntry`]s.
+const TICK_INTERVAL: TickDuration = TickDuration::from_secs(30);
+
+/// The cadence on which a [`ScheduleEntry`] re-fires.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", content = "data")]
+pub enum Cadence {
+	/// Fire every `seconds`, measured from the previously scheduled time (not
+	/// wall-clock), so a slow tick loop can't drift the cadence forward.
+	Interval { seconds: i64 },
+	/// A cron expression, evaluated against UTC.
+	Cron(String),
+}
+
+impl Cadence {
+	/// Rejects cadences that would never advance `next_run_at` forward (or
+	/// would walk it backward), which would otherwise re-dispatch the same
+	/// job on every tick forever.
+	fn validate(&self) -> Result<(), JobError> {
+		match self {
+			Self::Interval { seconds } if *seconds <= 0 => Err(JobError::InvalidScheduleCadence(
+				format!("interval seconds must be positive, got {seconds}"),
+			)),
+			Self::Interval { .. } => Ok(()),
+			Self::Cron(expr) => cron::Schedule::try_from(expr.as_str())
+				.map(|_| ())
+				.map_err(|e| JobError::InvalidCronExpression(expr.clone(), e.to_string())),
+		}
+	}
+
+	/// Computes the next instant this cadence should fire, given the instant it
+	/// was last scheduled to fire at.
+	fn next_after(&self, scheduled_at: DateTime<Utc>) -> Result<DateTime<Utc>, JobError> {
+		match self {
+			Self::Interval { seconds } => Ok(scheduled_at + Duration::seconds(*seconds)),
+			Self::Cron(expr) => {
+				let schedule = cron::Schedule::try_from(expr.as_str())
+					.map_err(|e| JobError::InvalidCronExpression(expr.clone(), e.to_string()))?;
+
+				schedule
+					.after(&scheduled_at)
+					.next()
+					.ok_or_else(|| JobError::InvalidCronExpression(expr.clone(), "no upcoming occurrence".to_string()))
+			}
+		}
+	}
+}
+
+/// A recurring job, persisted so it survives restarts of the node.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ScheduleEntry {
+	pub id: Uuid,
+	pub job_name: String,
+	pub job_args: Vec<u8>,
+	pub cadence: Cadence,
+	pub next_run_at: DateTime<Utc>,
+	pub last_run_id: Option<Uuid>,
+	pub enabled: bool,
+}
+
+impl From<schedule_entry::Data> for ScheduleEntry {
+	fn from(data: schedule_entry::Data) -> Self {
+		Self {
+			id: Uuid::from_slice(&data.id).expect("corrupted database"),
+			job_name: data.job_name,
+			job_args: data.job_args,
+			cadence: match (data.cron_expr, data.interval_seconds) {
+				(Some(cron_expr), _) => Cadence::Cron(cron_expr),
+				(None, Some(seconds)) if seconds > 0 => Cadence::Interval { seconds },
+				(None, _) => panic!("corrupted database: schedule_entry has no valid cadence"),
+			},
+			next_run_at: data.next_run_at.into(),
+			last_run_id: data
+				.last_run_id
+				.map(|id| Uuid::from_slice(&id).expect("corrupted database")),
+			enabled: data.enabled,
+		}
+	}
+}
+
+impl ScheduleEntry {
+	pub fn new(
+		job_name: String,
+		job_args: Vec<u8>,
+		cadence: Cadence,
+		starting_at: DateTime<Utc>,
+	) -> Result<Self, JobError> {
+		cadence.validate()?;
+
+		Ok(Self {
+			id: Uuid::new_v4(),
+			job_name,
+			job_args,
+			cadence,
+			next_run_at: starting_at,
+			last_run_id: None,
+			enabled: true,
+		})
+	}
+
+	pub async fn create(&self, library: &Library) -> Result<(), JobError> {
+		self.cadence.validate()?;
+
+		library
+			.db
+			.schedule_entry()
+			.create(
+				self.id.as_bytes().to_vec(),
+				self.job_name.clone(),
+				self.job_args.clone(),
+				self.next_run_at.into(),
+				self.enabled,
+				util::db::chain_optional_iter(
+					[],
+					[
+						match &self.cadence {
+							Cadence::Interval { seconds } => {
+								Some(schedule_entry::interval_seconds::set(Some(*seconds)))
+							}
+							Cadence::Cron(expr) => Some(schedule_entry::cron_expr::set(Some(expr.clone()))),
+						},
+					],
+				),
+			)
+			.exec()
+			.await?;
+
+		Ok(())
+	}
+
+	/// Flips `enabled`, persisting the change. Used by the `jobs.scheduler.pause`
+	/// and `jobs.scheduler.resume` rspc procedures.
+	pub async fn set_enabled(library: &Library, id: Uuid, enabled: bool) -> Result<(), JobError> {
+		library
+			.db
+			.schedule_entry()
+			.update(
+				schedule_entry::id::equals(id.as_bytes().to_vec()),
+				vec![schedule_entry::enabled::set(enabled)],
+			)
+			.exec()
+			.await?;
+
+		Ok(())
+	}
+
+	/// Backs the `jobs.scheduler.delete` rspc procedure.
+	pub async fn delete(library: &Library, id: Uuid) -> Result<(), JobError> {
+		library
+			.db
+			.schedule_entry()
+			.delete(schedule_entry::id::equals(id.as_bytes().to_vec()))
+			.exec()
+			.await?;
+
+		Ok(())
+	}
+
+	/// Advances `next_run_at` according to `cadence` and records the dispatched
+	/// job's id, persisting both to the database.
+	async fn advance(&mut self, run_id: Uuid, library: &Library) -> Result<(), JobError> {
+		self.last_run_id = Some(run_id);
+		self.next_run_at = self.cadence.next_after(self.next_run_at)?;
+
+		library
+			.db
+			.schedule_entry()
+			.update(
+				schedule_entry::id::equals(self.id.as_bytes().to_vec()),
+				vec![
+					schedule_entry::next_run_at::set(self.next_run_at.into()),
+					schedule_entry::last_run_id::set(Some(run_id.as_bytes().to_vec())),
+				],
+			)
+			.exec()
+			.await?;
+
+		Ok(())
+	}
+}
+
+/// Background task that dispatches recurring jobs registered through
+/// [`ScheduleEntry`]. One instance runs per [`Library`].
+pub struct JobScheduler;
+
+impl JobScheduler {
+	/// Spawns the tick loop. Intended to be called once, when a library is loaded.
+	pub fn spawn(library: Library) {
+		tokio::spawn(async move {
+			let mut ticker = interval(TICK_INTERVAL);
+
+			loop {
+				ticker.tick().await;
+
+				if let Err(e) = Self::tick(&library).await {
+					error!("Job scheduler tick failed: {e:#?}");
+				}
+			}
+		});
+	}
+
+	async fn tick(library: &Library) -> Result<(), JobError> {
+		let now = Utc::now();
+
+		let due = library
+			.db
+			.schedule_entry()
+			.find_many(vec![
+				schedule_entry::enabled::equals(true),
+				schedule_entry::next_run_at::lte(now.into()),
+			])
+			.exec()
+			.await?
+			.into_iter()
+			.map(ScheduleEntry::from);
+
+		for mut entry in due {
+			match library
+				.job_manager
+				.clone()
+				.dispatch_by_name(library, &entry.job_name, entry.job_args.clone())
+				.await
+			{
+				Ok(run_id) => {
+					if let Err(e) = entry.advance(run_id, library).await {
+						error!("Failed to advance schedule entry {}: {e:#?}", entry.id);
+					}
+				}
+				// A previous invocation is still running; skip this tick rather than
+				// queueing a duplicate, same guard the manual dispatch path uses.
+				Err(JobManagerError::AlreadyRunningJob { name, hash }) => {
+					warn!("Skipping scheduled run of '{name}' (hash={hash}), already running");
+				}
+				Err(e) => error!("Failed to dispatch scheduled job '{}': {e:#?}", entry.job_name),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn interval_next_after_advances_from_scheduled_time_not_wall_clock() {
+		let scheduled_at = "2024-01-01T00:00:00Z".parse().unwrap();
+		let cadence = Cadence::Interval { seconds: 60 };
+
+		assert_eq!(
+			cadence.next_after(scheduled_at).unwrap(),
+			scheduled_at + Duration::seconds(60)
+		);
+	}
+
+	#[test]
+	fn interval_validate_rejects_non_positive_seconds() {
+		assert!(Cadence::Interval { seconds: 0 }.validate().is_err());
+		assert!(Cadence::Interval { seconds: -1 }.validate().is_err());
+		assert!(Cadence::Interval { seconds: 1 }.validate().is_ok());
+	}
+
+	#[test]
+	fn cron_next_after_returns_a_later_instant() {
+		let scheduled_at: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+		// Every minute.
+		let cadence = Cadence::Cron("0 * * * * *".to_string());
+
+		let next = cadence.next_after(scheduled_at).unwrap();
+		assert!(next > scheduled_at);
+	}
+
+	#[test]
+	fn cron_validate_rejects_garbage_expressions() {
+		assert!(Cadence::Cron("not a cron expression".to_string())
+			.validate()
+			.is_err());
+	}
+}